@@ -0,0 +1,47 @@
+/// Builds the `matrix` uniform fed into every `Renderable`: an orthographic
+/// projection that keeps unit-quad geometry from stretching with the window,
+/// plus simple pan/zoom on top of it.
+pub struct Camera {
+    aspect: f32,
+    pan: [f32; 2],
+    zoom: f32,
+}
+
+impl Camera {
+    pub fn new(width: u32, height: u32) -> Camera {
+        Camera {
+            aspect: width as f32 / height.max(1) as f32,
+            pan: [0.0, 0.0],
+            zoom: 1.0,
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.aspect = width as f32 / height.max(1) as f32;
+    }
+
+    pub fn pan_by(&mut self, dx: f32, dy: f32) {
+        self.pan[0] += dx;
+        self.pan[1] += dy;
+    }
+
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom *= factor;
+    }
+
+    /// Column-major 4x4 matrix combining aspect correction, zoom and pan, ready
+    /// to upload as the `matrix` uniform that `assets/shade.vert` expects.
+    pub fn matrix(&self) -> [[f32; 4]; 4] {
+        let (sx, sy) = if self.aspect >= 1.0 {
+            (self.zoom / self.aspect, self.zoom)
+        } else {
+            (self.zoom, self.zoom * self.aspect)
+        };
+        [
+            [sx, 0.0, 0.0, 0.0],
+            [0.0, sy, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [self.pan[0] * sx, self.pan[1] * sy, 0.0, 1.0],
+        ]
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::{Context, Result};
+use glium::*;
+use std::sync::{Arc, Mutex};
+
+use crate::shader_program::ShaderProgram;
+use crate::{Renderable, TexVertex};
+
+/// A `Renderable` that streams a USB camera feed into its texture via the `uvc`
+/// crate, otherwise behaving exactly like `ImageQuad`.
+///
+/// `uvc::Context` -> `Device` -> `DeviceHandle` -> `ActiveStream` each borrow the
+/// previous one, so they can't all live as sibling fields of one struct without
+/// becoming self-referential. We sidestep that by leaking the context and device
+/// handle (`Box::leak`) to get `'static` borrows instead: this quad is expected
+/// to live for the whole program, so the leak is a one-time, bounded cost rather
+/// than an ongoing one.
+pub struct CameraQuad {
+    vertices: glium::vertex::VertexBuffer<TexVertex>,
+    indices: glium::IndexBuffer<u32>,
+    texture: glium::texture::Texture2d,
+    program: ShaderProgram,
+    pending_frame: Arc<Mutex<Option<glium::texture::RawImage2d<'static, u8>>>>,
+    frame_width: u32,
+    frame_height: u32,
+    // Keeps the uvc stream alive for as long as the quad exists; the streaming
+    // callback writes into `pending_frame` from a uvc-owned thread.
+    _stream: uvc::ActiveStream<'static, StreamUserData>,
+}
+
+struct StreamUserData {
+    pending_frame: Arc<Mutex<Option<glium::texture::RawImage2d<'static, u8>>>>,
+}
+
+fn frame_callback(frame: &uvc::Frame, user_data: &mut StreamUserData) {
+    let rgb = match frame.to_rgb() {
+        Ok(rgb) => rgb,
+        Err(_) => return,
+    };
+    let (width, height) = (rgb.width(), rgb.height());
+    let raw = glium::texture::RawImage2d::from_raw_rgb(rgb.to_bytes().to_vec(), (width, height));
+
+    if let Ok(mut pending) = user_data.pending_frame.lock() {
+        *pending = Some(raw);
+    }
+}
+
+impl CameraQuad {
+    /// `width`/`height`/`fps` are the preferred stream settings; the texture is
+    /// sized off whatever the camera actually negotiates, which may differ.
+    pub fn new<F: glium::backend::Facade>(
+        facade: &F,
+        width: u32,
+        height: u32,
+        fps: u32,
+    ) -> Result<CameraQuad> {
+        let context: &'static uvc::Context<'static> =
+            Box::leak(Box::new(uvc::Context::new().context("could not open libuvc context")?));
+        let device: &'static uvc::Device<'static> = Box::leak(Box::new(
+            context
+                .find_device(None, None, None)
+                .context("could not find a USB camera")?,
+        ));
+        let device_handle: &'static uvc::DeviceHandle<'static> =
+            Box::leak(Box::new(device.open().context("could not open camera device")?));
+
+        let pending_frame = Arc::new(Mutex::new(None));
+        let user_data = StreamUserData {
+            pending_frame: pending_frame.clone(),
+        };
+
+        let format = device_handle
+            .get_preferred_format(|candidate, default| {
+                if candidate.width == width && candidate.height == height && candidate.fps == fps {
+                    candidate
+                } else {
+                    default
+                }
+            })
+            .context("camera has no usable format")?;
+        let (frame_width, frame_height) = (format.width, format.height);
+
+        let stream_handle = device_handle
+            .get_stream_handle_with_format(format)
+            .context("could not negotiate camera format")?;
+        let stream = stream_handle
+            .start_stream(frame_callback, user_data)
+            .context("could not start camera stream")?;
+
+        let shape = [
+            TexVertex {
+                position: [0.0, 0.0],
+                tex_coords: [0.0, 0.0],
+            },
+            TexVertex {
+                position: [0.0, 1.0],
+                tex_coords: [0.0, 1.0],
+            },
+            TexVertex {
+                position: [0.1, 1.0],
+                tex_coords: [1.0, 1.0],
+            },
+            TexVertex {
+                position: [0.1, 0.0],
+                tex_coords: [1.0, 0.0],
+            },
+        ];
+        let vertices = glium::VertexBuffer::persistent(facade, &shape).context("no vertices")?;
+        let indices = glium::IndexBuffer::new(
+            facade,
+            glium::index::PrimitiveType::TrianglesList,
+            &[0u32, 1, 2, 0, 2, 3],
+        )
+        .context("no index")?;
+
+        let program = ShaderProgram::new(facade, "assets/shade.vert", "assets/shade.frag")
+            .context("could not load camera quad shaders")?;
+
+        let blank = glium::texture::RawImage2d::from_raw_rgb(
+            vec![0u8; (frame_width * frame_height * 3) as usize],
+            (frame_width, frame_height),
+        );
+        let texture = glium::texture::Texture2d::new(facade, blank).context("no camera texture")?;
+
+        Ok(CameraQuad {
+            vertices,
+            indices,
+            texture,
+            program,
+            pending_frame,
+            frame_width,
+            frame_height,
+            _stream: stream,
+        })
+    }
+}
+
+impl Renderable for CameraQuad {
+    fn update<F: glium::backend::Facade>(&mut self, facade: &F, _delta: std::time::Duration) -> Result<()> {
+        let frame = self
+            .pending_frame
+            .lock()
+            .map_err(|_| anyhow::anyhow!("camera frame mutex poisoned"))?
+            .take();
+
+        if let Some(raw) = frame {
+            if raw.width == self.frame_width && raw.height == self.frame_height {
+                self.texture.write(
+                    glium::Rect {
+                        left: 0,
+                        bottom: 0,
+                        width: raw.width,
+                        height: raw.height,
+                    },
+                    raw,
+                );
+            }
+        }
+
+        self.program.poll(facade)
+    }
+
+    fn custom_render<S: glium::Surface>(
+        &self,
+        frame: &mut S,
+        matrix: [[f32; 4]; 4],
+        params: &glium::draw_parameters::DrawParameters,
+    ) -> Result<(), glium::DrawError> {
+        let uniforms = uniform! { image: &self.texture, matrix: matrix };
+
+        frame.draw(
+            &self.vertices,
+            &self.indices,
+            self.program.program(),
+            &uniforms,
+            params,
+        )
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use image::{DynamicImage, GenericImageView};
+
+const WORKGROUP_SIZE: u32 = 16;
+
+const COMPUTE_SHADER_SRC: &str = r#"
+    #version 430
+    layout(local_size_x = 16, local_size_y = 16, local_size_z = 1) in;
+
+    layout(std140, binding = 0) buffer Pixels {
+        vec4 pixels[];
+    };
+
+    uniform ivec2 image_size;
+    uniform float power;
+    uniform int op;
+
+    const int OP_GAMMA = 0;
+    const int OP_THRESHOLD = 1;
+
+    void main() {
+        ivec2 coord = ivec2(gl_GlobalInvocationID.xy);
+        if (coord.x >= image_size.x || coord.y >= image_size.y) {
+            return;
+        }
+
+        uint idx = uint(coord.y) * uint(image_size.x) + uint(coord.x);
+        vec4 color = pixels[idx];
+
+        if (op == OP_GAMMA) {
+            pixels[idx] = vec4(pow(color.rgb, vec3(power)), color.a);
+        } else if (op == OP_THRESHOLD) {
+            pixels[idx] = vec4(step(vec3(power), color.rgb), color.a);
+        }
+    }
+"#;
+
+/// A GPU image filter dispatched by `make_things_from_image`. Both variants carry
+/// the same `f32` the compute shader reads as `power`, just interpreted
+/// differently (exponent for gamma, cutoff for threshold).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ImageOp {
+    Gamma(f32),
+    Threshold(f32),
+}
+
+impl ImageOp {
+    fn power(&self) -> f32 {
+        match self {
+            ImageOp::Gamma(power) => *power,
+            ImageOp::Threshold(cutoff) => *cutoff,
+        }
+    }
+
+    fn code(&self) -> i32 {
+        match self {
+            ImageOp::Gamma(_) => 0,
+            ImageOp::Threshold(_) => 1,
+        }
+    }
+}
+
+/// The same transform as `COMPUTE_SHADER_SRC`, run on the CPU; used to check the
+/// GPU pipeline's output in tests.
+fn cpu_apply(op: ImageOp, pixels: &[[f32; 4]]) -> Vec<[f32; 4]> {
+    pixels
+        .iter()
+        .map(|&[r, g, b, a]| match op {
+            ImageOp::Gamma(power) => [r.powf(power), g.powf(power), b.powf(power), a],
+            ImageOp::Threshold(cutoff) => {
+                let step = |c: f32| if c >= cutoff { 1.0 } else { 0.0 };
+                [step(r), step(g), step(b), a]
+            }
+        })
+        .collect()
+}
+
+fn to_pixels(img: &DynamicImage) -> (Vec<[f32; 4]>, (u32, u32)) {
+    let dims = img.dimensions();
+    let pixels = img
+        .to_rgba32f()
+        .pixels()
+        .map(|p| p.0)
+        .collect::<Vec<_>>();
+    (pixels, dims)
+}
+
+/// Dispatches `op` on `img` via the compute shader above: uploads the decoded
+/// pixels into a `std140` shader-storage buffer, runs one invocation per pixel
+/// (work groups sized off the image dimensions), reads the results back and
+/// builds a displayable `Texture2d` from them.
+pub fn make_things_from_image<F: glium::backend::Facade>(
+    facade: &F,
+    img: &DynamicImage,
+    op: ImageOp,
+) -> Result<glium::texture::Texture2d> {
+    let program = glium::program::ComputeShader::from_source(facade, COMPUTE_SHADER_SRC)
+        .context("no compute shader")?;
+
+    let (pixels, (width, height)) = to_pixels(img);
+
+    let mut buffer: glium::uniforms::UniformBuffer<[[f32; 4]]> =
+        glium::uniforms::UniformBuffer::empty_unsized(facade, pixels.len() * std::mem::size_of::<[f32; 4]>())
+            .context("could not allocate pixel buffer")?;
+    {
+        let mut mapping = buffer.map();
+        for (dst, src) in mapping.iter_mut().zip(pixels.iter()) {
+            *dst = *src;
+        }
+    }
+
+    let groups_x = width.div_ceil(WORKGROUP_SIZE);
+    let groups_y = height.div_ceil(WORKGROUP_SIZE);
+
+    program.execute(
+        glium::uniform! {
+            Pixels: &*buffer,
+            image_size: [width as i32, height as i32],
+            power: op.power(),
+            op: op.code(),
+        },
+        groups_x,
+        groups_y,
+        1,
+    );
+
+    let processed: Vec<[f32; 4]> = buffer.read().context("could not read back pixel buffer")?;
+    let raw = glium::texture::RawImage2d::from_raw_rgba(
+        processed.into_iter().flatten().collect(),
+        (width, height),
+    );
+    glium::texture::Texture2d::with_mipmaps(
+        facade,
+        raw,
+        glium::texture::MipmapsOption::AutoGeneratedMipmaps,
+    )
+    .context("could not build processed texture")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_pixel_approx_eq(actual: [f32; 4], expected: [f32; 4]) {
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-6, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn cpu_gamma_matches_pow() {
+        let pixels = [[0.25, 0.5, 1.0, 1.0], [0.0, 1.0, 0.8, 0.5]];
+        let result = cpu_apply(ImageOp::Gamma(2.0), &pixels);
+        assert_pixel_approx_eq(result[0], [0.0625, 0.25, 1.0, 1.0]);
+        assert_pixel_approx_eq(result[1], [0.0, 1.0, 0.64, 0.5]);
+    }
+
+    #[test]
+    fn cpu_threshold_matches_step() {
+        let pixels = [[0.2, 0.6, 0.9, 1.0]];
+        let result = cpu_apply(ImageOp::Threshold(0.5), &pixels);
+        assert_eq!(result[0], [0.0, 1.0, 1.0, 1.0]);
+    }
+
+    // The GPU path needs a real GL 4.3 context (compute shaders), which isn't
+    // available on every CI runner; run explicitly with `cargo test -- --ignored`
+    // on a machine with a GPU to compare it against `cpu_apply`.
+    #[test]
+    #[ignore]
+    fn gpu_matches_cpu_for_gamma() {
+        use glium::glutin;
+
+        let event_loop = glutin::event_loop::EventLoop::new();
+        let context = glutin::ContextBuilder::new()
+            .build_headless(&event_loop, glutin::dpi::PhysicalSize::new(1, 1))
+            .expect("no headless GL context available");
+        let context = unsafe { context.make_current() }.unwrap();
+        let facade = glium::backend::glutin::headless::Headless::new(context).unwrap();
+
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            4,
+            4,
+            image::Rgba([64, 128, 255, 255]),
+        ));
+        let op = ImageOp::Gamma(2.2);
+
+        let (pixels, _) = to_pixels(&img);
+        let expected = cpu_apply(op, &pixels);
+
+        let texture = make_things_from_image(&facade, &img, op).unwrap();
+        let actual: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+
+        for (row, expected_row) in actual.iter().zip(expected.chunks(4)) {
+            for (px, expected_px) in row.iter().zip(expected_row.iter()) {
+                let got = [
+                    px.0 as f32 / 255.0,
+                    px.1 as f32 / 255.0,
+                    px.2 as f32 / 255.0,
+                    px.3 as f32 / 255.0,
+                ];
+                for (g, e) in got.iter().zip(expected_px.iter()) {
+                    assert!((g - e).abs() < 0.01, "got {g}, expected {e}");
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,24 @@
 use anyhow::{Context, Result};
 use glium::*;
-use image::{DynamicImage, EncodableLayout, GenericImageView};
+use image::{EncodableLayout, GenericImageView};
 use resource::resource;
+use std::rc::Rc;
+
+mod camera;
+mod camera_quad;
+mod compute_image;
+mod post_process;
+mod shader_program;
+mod text;
+mod texture_atlas;
+use camera::Camera;
+use camera_quad::CameraQuad;
+use compute_image::{make_things_from_image, ImageOp};
+use post_process::{CrtSettings, PostProcess};
+use shader_program::ShaderProgram;
+use std::cell::RefCell;
+use text::{GlyphCache, Text};
+use texture_atlas::TextureAtlas;
 
 #[derive(Copy, Clone)]
 struct Vertex {
@@ -9,23 +26,33 @@ struct Vertex {
 }
 implement_vertex!(Vertex, position);
 
+#[derive(Copy, Clone)]
+struct TexVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+}
+implement_vertex!(TexVertex, position, tex_coords);
+
 trait Renderable {
-    fn update(&mut self, delta: std::time::Duration) -> Result<()>;
-    fn render(&self, frame: &mut Frame) -> Result<(), glium::DrawError>;
-    fn custom_render(
+    fn update<F: glium::backend::Facade>(&mut self, facade: &F, delta: std::time::Duration) -> Result<()>;
+    fn render<S: glium::Surface>(&self, surface: &mut S, matrix: [[f32; 4]; 4]) -> Result<(), glium::DrawError> {
+        self.custom_render(surface, matrix, &Default::default())
+    }
+    fn custom_render<S: glium::Surface>(
         &self,
-        frame: &mut Frame,
+        surface: &mut S,
+        matrix: [[f32; 4]; 4],
         params: &glium::draw_parameters::DrawParameters,
     ) -> Result<(), glium::DrawError>;
 }
 
 struct RedTriangle {
     vertices: glium::vertex::VertexBuffer<Vertex>,
-    program: glium::program::Program,
+    program: ShaderProgram,
 }
 
 impl RedTriangle {
-    fn new<F: glium::backend::Facade>(facade: &F) -> RedTriangle {
+    fn new<F: glium::backend::Facade>(facade: &F) -> Result<RedTriangle> {
         let shape = vec![
             Vertex {
                 position: [-0.5, -0.5],
@@ -38,54 +65,37 @@ impl RedTriangle {
             },
         ];
         let vertex_buffer = glium::VertexBuffer::persistent(facade, &shape).unwrap();
-        let vertex_shader_src = r#"
-    #version 140
-
-    in vec2 position;
-
-    void main() {
-        gl_Position = vec4(position, 0.0, 1.0);
-    }
-"#;
-        let fragment_shader_src = r#"
-    #version 140
-
-    out vec4 color;
-
-    void main() {
-        color = vec4(1.0, 0.0, 0.0, 1.0);
-    }
-"#;
-        let program =
-            glium::Program::from_source(facade, vertex_shader_src, fragment_shader_src, None)
-                .unwrap();
+        let program = ShaderProgram::new(
+            facade,
+            "assets/red_triangle.vert",
+            "assets/red_triangle.frag",
+        )
+        .context("could not load red triangle shaders")?;
 
-        RedTriangle {
+        Ok(RedTriangle {
             vertices: vertex_buffer,
-            program: program,
-        }
+            program,
+        })
     }
 }
 
 impl Renderable for RedTriangle {
-    fn render(&self, frame: &mut Frame) -> Result<(), glium::DrawError> {
-        self.custom_render(frame, &Default::default())
-    }
-    fn custom_render(
+    fn custom_render<S: glium::Surface>(
         &self,
-        frame: &mut Frame,
+        frame: &mut S,
+        matrix: [[f32; 4]; 4],
         params: &glium::draw_parameters::DrawParameters,
     ) -> Result<(), glium::DrawError> {
         frame.draw(
             &self.vertices,
             glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
-            &self.program,
-            &glium::uniforms::EmptyUniforms,
+            self.program.program(),
+            &uniform! { matrix: matrix },
             &params,
         )
     }
 
-    fn update(&mut self, delta: std::time::Duration) -> Result<()> {
+    fn update<F: glium::backend::Facade>(&mut self, facade: &F, delta: std::time::Duration) -> Result<()> {
         let mut data = self
             .vertices
             .read()
@@ -94,7 +104,7 @@ impl Renderable for RedTriangle {
         data[0].position[0] = (delta.as_nanos() as f32).cos();
         self.vertices.write(&data);
 
-        Ok(())
+        self.program.poll(facade)
     }
 }
 
@@ -109,53 +119,31 @@ fn load_image(raw_data: &[u8]) -> Result<image::DynamicImage> {
     reader.decode().context("must decode")
 }
 
-fn make_things_from_image<F: glium::backend::Facade>(
-    facade: &F,
-    _img: &DynamicImage,
-) -> Result<()> {
-    let _program = glium::program::ComputeShader::from_source(
-        facade,
-        r#"\
-    #version 430
-    layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
-    layout(std140) buffer MyBlock {
-        float power;
-        vec4 values[4096/4];
-    };
-    void main() {
-        vec4 val = values[gl_GlobalInvocationID.x];
-        values[gl_GlobalInvocationID.x] = pow(val, vec4(power));
-    }
-"#,
-    )
-    .context("no compute shader")?;
-
-    Ok(())
-}
 
 struct ImageQuad {
-    vertices: glium::vertex::VertexBuffer<Vertex>,
+    vertices: glium::vertex::VertexBuffer<TexVertex>,
     indices: glium::IndexBuffer<u32>,
     texture: glium::texture::Texture2d,
-    program: glium::program::Program,
+    program: ShaderProgram,
 }
 
 impl ImageQuad {
+    fn program<F: glium::backend::Facade>(facade: &F) -> Result<ShaderProgram> {
+        ShaderProgram::new(facade, "assets/shade.vert", "assets/shade.frag")
+            .context("could not load image quad shaders")
+    }
+
     fn new<F: glium::backend::Facade>(facade: &F, img: &image::DynamicImage) -> Result<ImageQuad> {
-        let shape = vec![
-            Vertex {
-                position: [0.0, 0.0],
-            },
-            Vertex {
-                position: [0.0, 1.0],
-            },
-            Vertex {
-                position: [0.1, 1.0],
-            },
-            Vertex {
-                position: [0.1, 0.0],
-            },
-        ];
+        let positions = [[0.0, 0.0], [0.0, 1.0], [0.1, 1.0], [0.1, 0.0]];
+        let uvs = [[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]];
+        let shape: Vec<TexVertex> = positions
+            .into_iter()
+            .zip(uvs)
+            .map(|(position, tex_coords)| TexVertex {
+                position,
+                tex_coords,
+            })
+            .collect();
         let vertices = glium::VertexBuffer::persistent(facade, &shape).context("no vertices")?;
         let data = [0u32, 1, 2, 0, 2, 3];
 
@@ -163,35 +151,7 @@ impl ImageQuad {
             glium::IndexBuffer::new(facade, glium::index::PrimitiveType::TrianglesList, &data)
                 .context("no index")?;
 
-        let vertex_shader_src = r#"
-    #version 430
-
-    in vec2 position;
-    
-    smooth out vec2 coords;
-    
-    void main() {
-        gl_Position = vec4(position,0.0, 1.0); 
-        coords = position;
-    }
-        
-"#;
-        let fragment_shader_src = r#"
-    #version 430
-
-    uniform sampler2D image;
-    
-    smooth in vec2 coords;
-    out vec4 frag_color;
-    
-    void main() {
-        frag_color = texture(image, coords);
-    }
-        
-"#;
-        let program =
-            glium::Program::from_source(facade, vertex_shader_src, fragment_shader_src, None)
-                .context("no program")?;
+        let program = Self::program(facade)?;
 
         let image =
             glium::texture::RawImage2d::from_raw_rgba(img.to_rgba8().into_raw(), img.dimensions());
@@ -209,34 +169,192 @@ impl ImageQuad {
             program,
         })
     }
+
+    /// Builds a quad at `position`..`position + size` displaying an
+    /// already-built texture, e.g. the output of `compute_image::make_things_from_image`.
+    fn from_texture<F: glium::backend::Facade>(
+        facade: &F,
+        texture: glium::texture::Texture2d,
+        position: [f32; 2],
+        size: [f32; 2],
+    ) -> Result<ImageQuad> {
+        let [x, y] = position;
+        let [w, h] = size;
+        let shape = [
+            TexVertex {
+                position: [x, y],
+                tex_coords: [0.0, 0.0],
+            },
+            TexVertex {
+                position: [x, y + h],
+                tex_coords: [0.0, 1.0],
+            },
+            TexVertex {
+                position: [x + w, y + h],
+                tex_coords: [1.0, 1.0],
+            },
+            TexVertex {
+                position: [x + w, y],
+                tex_coords: [1.0, 0.0],
+            },
+        ];
+        let vertices = glium::VertexBuffer::persistent(facade, &shape).context("no vertices")?;
+        let indices = glium::IndexBuffer::new(
+            facade,
+            glium::index::PrimitiveType::TrianglesList,
+            &[0u32, 1, 2, 0, 2, 3],
+        )
+        .context("no index")?;
+
+        let program = Self::program(facade)?;
+
+        Ok(ImageQuad {
+            vertices,
+            indices,
+            texture,
+            program,
+        })
+    }
 }
 
 impl Renderable for ImageQuad {
-    fn update(&mut self, _delta: std::time::Duration) -> Result<()> {
-        Ok(())
+    fn update<F: glium::backend::Facade>(&mut self, facade: &F, _delta: std::time::Duration) -> Result<()> {
+        self.program.poll(facade)
+    }
+
+    fn custom_render<S: glium::Surface>(
+        &self,
+        frame: &mut S,
+        matrix: [[f32; 4]; 4],
+        params: &glium::draw_parameters::DrawParameters,
+    ) -> Result<(), glium::DrawError> {
+        let uniforms = uniform! { image: &self.texture, matrix: matrix };
+        frame.draw(
+            &self.vertices,
+            &self.indices,
+            self.program.program(),
+            &uniforms,
+            params,
+        )
     }
+}
+
+/// Draws many sprites packed into one [`TextureAtlas`] with a single combined
+/// vertex/index buffer, so N atlas sprites cost one draw call instead of N.
+struct AtlasBatch {
+    vertices: glium::vertex::VertexBuffer<TexVertex>,
+    indices: glium::IndexBuffer<u32>,
+    texture: Rc<glium::texture::SrgbTexture2d>,
+    program: ShaderProgram,
+}
+
+impl AtlasBatch {
+    /// `sprites` is `(name, position, size)` for each quad to place in the batch.
+    fn new<F: glium::backend::Facade>(
+        facade: &F,
+        atlas: &TextureAtlas,
+        sprites: &[(&str, [f32; 2], [f32; 2])],
+    ) -> Result<AtlasBatch> {
+        let mut shape = Vec::with_capacity(sprites.len() * 4);
+        let mut data = Vec::with_capacity(sprites.len() * 6);
+
+        for (name, position, size) in sprites {
+            let uv = atlas
+                .uv_of(name)
+                .with_context(|| format!("{name} is not packed into the atlas"))?;
+            let [x, y] = *position;
+            let [w, h] = *size;
+            let base = shape.len() as u32;
+
+            shape.push(TexVertex {
+                position: [x, y],
+                tex_coords: uv.bottom_left,
+            });
+            shape.push(TexVertex {
+                position: [x, y + h],
+                tex_coords: uv.top_left,
+            });
+            shape.push(TexVertex {
+                position: [x + w, y + h],
+                tex_coords: uv.top_right,
+            });
+            shape.push(TexVertex {
+                position: [x + w, y],
+                tex_coords: uv.bottom_right,
+            });
+            data.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
 
-    fn render(&self, frame: &mut Frame) -> Result<(), glium::DrawError> {
-        self.custom_render(frame, &Default::default())
+        let vertices = glium::VertexBuffer::persistent(facade, &shape).context("no vertices")?;
+        let indices =
+            glium::IndexBuffer::new(facade, glium::index::PrimitiveType::TrianglesList, &data)
+                .context("no index")?;
+        let program = ImageQuad::program(facade)?;
+
+        Ok(AtlasBatch {
+            vertices,
+            indices,
+            texture: atlas.texture_rc(),
+            program,
+        })
     }
+}
 
-    fn custom_render(
+impl Renderable for AtlasBatch {
+    fn update<F: glium::backend::Facade>(&mut self, facade: &F, _delta: std::time::Duration) -> Result<()> {
+        self.program.poll(facade)
+    }
+
+    fn custom_render<S: glium::Surface>(
         &self,
-        frame: &mut Frame,
+        frame: &mut S,
+        matrix: [[f32; 4]; 4],
         params: &glium::draw_parameters::DrawParameters,
     ) -> Result<(), glium::DrawError> {
-        let uniforms = uniform! {
-        image: &self.texture };
-
+        let uniforms = uniform! { image: self.texture.as_ref(), matrix: matrix };
         frame.draw(
             &self.vertices,
             &self.indices,
-            &self.program,
+            self.program.program(),
             &uniforms,
             params,
-        )?;
+        )
+    }
+}
+
+/// Font + shared glyph cache/program backing the fps overlay. Loaded from disk
+/// (rather than `resource!`, which embeds at compile time) so a missing font
+/// is a recoverable `Err` rather than a build-time failure.
+struct DebugOverlay {
+    // Leaked to get a `&'static Font<'static>`: `GlyphCache`'s `gpu_cache::Cache`
+    // is itself `'static`, which requires every glyph queued into it to be too.
+    // This overlay is expected to live for the whole program, so the leak is a
+    // one-time, bounded cost rather than an ongoing one (same tradeoff as
+    // `CameraQuad`'s leaked `uvc` handles).
+    font: &'static rusttype::Font<'static>,
+    glyph_cache: Rc<RefCell<GlyphCache>>,
+    program: Rc<RefCell<ShaderProgram>>,
+}
 
-        Ok(())
+impl DebugOverlay {
+    fn new<F: glium::backend::Facade>(facade: &F) -> Result<DebugOverlay> {
+        let font_bytes = std::fs::read("assets/DejaVuSans.ttf")
+            .context("could not read assets/DejaVuSans.ttf")?;
+        let font = rusttype::Font::try_from_vec(font_bytes)
+            .context("could not parse debug overlay font")?;
+        let font: &'static rusttype::Font<'static> = Box::leak(Box::new(font));
+        let glyph_cache = Rc::new(RefCell::new(
+            GlyphCache::new(facade, 512, 512).context("could not construct glyph cache")?,
+        ));
+        let program = Rc::new(RefCell::new(
+            Text::program(facade).context("could not construct text program")?,
+        ));
+
+        Ok(DebugOverlay {
+            font,
+            glyph_cache,
+            program,
+        })
     }
 }
 
@@ -246,14 +364,68 @@ fn main() -> Result<()> {
     let cb = glutin::ContextBuilder::new();
     let display = glium::Display::new(wb, cb, &event_loop).unwrap();
 
-    let mut inabox = Box::new(RedTriangle::new(&display));
+    let mut inabox = Box::new(RedTriangle::new(&display).context("must construct")?);
     let mut last_time = std::time::Instant::now();
 
     let asset = resource!("assets/D18.png");
     let img = load_image(asset.as_bytes())?;
-    make_things_from_image(&display, &img).unwrap();
+    let processed = make_things_from_image(&display, &img, ImageOp::Gamma(2.2))
+        .context("compute shader pass failed")?;
+
+    let mut quad = ImageQuad::new(&display, &img).expect("must construct");
+    let mut processed_quad =
+        ImageQuad::from_texture(&display, processed, [0.2, 0.0], [0.1, 1.0])
+            .expect("must construct processed quad");
+
+    let mut camera_quad = match CameraQuad::new(&display, 640, 480, 30) {
+        Ok(camera_quad) => Some(camera_quad),
+        Err(err) => {
+            eprintln!("no camera available, skipping live feed: {err:#}");
+            None
+        }
+    };
 
-    let quad = ImageQuad::new(&display, &img).expect("must construct");
+    // Demo the atlas path: pack every swatch under assets/textures into one
+    // texture and draw both of them in a single AtlasBatch draw call, so the
+    // atlas code is actually exercised and really does share one draw.
+    let mut atlas_quad = match TextureAtlas::from_glob(&display, "assets/textures/**/*.png", 1024) {
+        Ok(atlas) => {
+            let sprites = [
+                ("red_swatch", [-1.0, -1.0], [0.1, 0.1]),
+                ("blue_swatch", [-0.85, -1.0], [0.1, 0.1]),
+            ];
+            match AtlasBatch::new(&display, &atlas, &sprites) {
+                Ok(batch) => Some(batch),
+                Err(err) => {
+                    eprintln!("could not build atlas batch, skipping: {err:#}");
+                    None
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!("no texture atlas available, skipping: {err:#}");
+            None
+        }
+    };
+
+    let window_size = display.gl_window().window().inner_size();
+    let mut post_process = PostProcess::new(&display, window_size.width, window_size.height)
+        .expect("must construct post-process pass");
+    let crt_settings = CrtSettings::default();
+    let mut camera = Camera::new(window_size.width, window_size.height);
+
+    // Like the camera feed and texture atlas above, the fps overlay is optional:
+    // a missing/unreadable font shouldn't take down the whole renderer.
+    let debug_overlay = match DebugOverlay::new(&display) {
+        Ok(overlay) => Some(overlay),
+        Err(err) => {
+            eprintln!("no debug overlay font available, skipping fps overlay: {err:#}");
+            None
+        }
+    };
+    let mut fps_text: Option<Text> = None;
+    let mut fps_accum = std::time::Duration::ZERO;
+    let mut fps_frames = 0u32;
 
     event_loop.run(move |ev, _, control_flow| {
         let now = std::time::Instant::now();
@@ -262,13 +434,84 @@ fn main() -> Result<()> {
         let next_frame_time =
             std::time::Instant::now() + std::time::Duration::from_nanos(16_666_667);
 
-        let mut target = display.draw();
-        target.clear_color(0.0, 0.0, 1.0, 1.0);
+        inabox.update(&display, delta).context("must update").unwrap();
+        quad.update(&display, delta).context("must update quad").unwrap();
+        processed_quad
+            .update(&display, delta)
+            .context("must update processed quad")
+            .unwrap();
+        if let Some(camera_quad) = &mut camera_quad {
+            camera_quad
+                .update(&display, delta)
+                .context("must update camera")
+                .unwrap();
+        }
+        if let Some(atlas_quad) = &mut atlas_quad {
+            atlas_quad
+                .update(&display, delta)
+                .context("must update atlas quad")
+                .unwrap();
+        }
+        if let Some(fps_text) = &mut fps_text {
+            fps_text
+                .update(&display, delta)
+                .context("must update fps text")
+                .unwrap();
+        }
 
-        quad.render(&mut target).unwrap();
+        fps_accum += delta;
+        fps_frames += 1;
+        if let Some(overlay) = &debug_overlay {
+            if fps_accum >= std::time::Duration::from_secs(1) {
+                let fps = fps_frames as f32 / fps_accum.as_secs_f32();
+                let window_size = display.gl_window().window().inner_size();
+                fps_text = Text::new(
+                    &display,
+                    overlay.glyph_cache.clone(),
+                    overlay.program.clone(),
+                    overlay.font,
+                    &format!("{fps:.0} fps"),
+                    [-1.0, 1.0],
+                    24.0,
+                    (window_size.width as f32, window_size.height as f32),
+                    None,
+                )
+                .context("must lay out fps text")
+                .ok();
+                fps_accum = std::time::Duration::ZERO;
+                fps_frames = 0;
+            }
+        }
 
-        inabox.update(delta).context("must update").unwrap();
-        inabox.render(&mut target).context("render error").unwrap();
+        let matrix = camera.matrix();
+        let mut target = display.draw();
+        target.clear_color(0.0, 0.0, 0.0, 1.0);
+
+        post_process
+            .draw(&display, &mut target, &crt_settings, |scene| {
+                scene.clear_color(0.0, 0.0, 1.0, 1.0);
+                quad.render(scene, matrix)?;
+                processed_quad.render(scene, matrix)?;
+                inabox.render(scene, matrix)?;
+                if let Some(camera_quad) = &camera_quad {
+                    camera_quad.render(scene, matrix)?;
+                }
+                if let Some(atlas_quad) = &atlas_quad {
+                    atlas_quad.render(scene, matrix)?;
+                }
+                if let Some(fps_text) = &fps_text {
+                    let identity = [
+                        [1.0, 0.0, 0.0, 0.0],
+                        [0.0, 1.0, 0.0, 0.0],
+                        [0.0, 0.0, 1.0, 0.0],
+                        [0.0, 0.0, 0.0, 1.0],
+                    ];
+                    fps_text.render(scene, identity)?;
+                }
+                Ok(())
+            })
+            .context("post-process pass failed")
+            .unwrap();
 
         target.finish().unwrap();
 
@@ -279,6 +522,14 @@ fn main() -> Result<()> {
                     *control_flow = glutin::event_loop::ControlFlow::Exit;
                     return;
                 }
+                glutin::event::WindowEvent::Resized(size) => {
+                    camera.resize(size.width, size.height);
+                    post_process
+                        .resize(&display, size.width, size.height)
+                        .context("must resize post-process pass")
+                        .unwrap();
+                    return;
+                }
                 _ => return,
             },
             _ => (),
@@ -0,0 +1,134 @@
+use anyhow::{Context, Result};
+use glium::*;
+
+use crate::shader_program::ShaderProgram;
+
+#[derive(Copy, Clone)]
+struct ScreenVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+}
+implement_vertex!(ScreenVertex, position, tex_coords);
+
+/// Configurable knobs for the CRT look; tune and feed straight into [`PostProcess::draw`].
+#[derive(Copy, Clone)]
+pub struct CrtSettings {
+    pub curvature: f32,
+    pub scanline_opacity: f32,
+    pub brightness: f32,
+}
+
+impl Default for CrtSettings {
+    fn default() -> Self {
+        CrtSettings {
+            curvature: 0.15,
+            scanline_opacity: 0.3,
+            brightness: 1.1,
+        }
+    }
+}
+
+/// Renders the scene into an offscreen texture, then runs it through a CRT fragment
+/// shader (barrel distortion, scanlines, brightness) onto a fullscreen quad.
+pub struct PostProcess {
+    scene_texture: glium::texture::Texture2d,
+    quad: glium::vertex::VertexBuffer<ScreenVertex>,
+    indices: glium::IndexBuffer<u32>,
+    program: ShaderProgram,
+}
+
+impl PostProcess {
+    pub fn new<F: glium::backend::Facade>(facade: &F, width: u32, height: u32) -> Result<PostProcess> {
+        let scene_texture = glium::texture::Texture2d::empty(facade, width, height)
+            .context("could not allocate offscreen scene texture")?;
+
+        let shape = [
+            ScreenVertex {
+                position: [-1.0, -1.0],
+                tex_coords: [0.0, 0.0],
+            },
+            ScreenVertex {
+                position: [-1.0, 1.0],
+                tex_coords: [0.0, 1.0],
+            },
+            ScreenVertex {
+                position: [1.0, 1.0],
+                tex_coords: [1.0, 1.0],
+            },
+            ScreenVertex {
+                position: [1.0, -1.0],
+                tex_coords: [1.0, 0.0],
+            },
+        ];
+        let quad = glium::VertexBuffer::persistent(facade, &shape).context("no vertices")?;
+        let indices = glium::IndexBuffer::new(
+            facade,
+            glium::index::PrimitiveType::TrianglesList,
+            &[0u32, 1, 2, 0, 2, 3],
+        )
+        .context("no index")?;
+
+        let program = ShaderProgram::new(facade, "assets/crt.vert", "assets/crt.frag")
+            .context("could not load post-process shaders")?;
+
+        Ok(PostProcess {
+            scene_texture,
+            quad,
+            indices,
+            program,
+        })
+    }
+
+    /// Reallocates the offscreen scene texture for a new window size. Call this
+    /// from the window's resize handler, or the scene keeps rendering at the old
+    /// resolution (and aspect ratio) after the window changes size.
+    pub fn resize<F: glium::backend::Facade>(&mut self, facade: &F, width: u32, height: u32) -> Result<()> {
+        if self.scene_texture.width() == width && self.scene_texture.height() == height {
+            return Ok(());
+        }
+
+        self.scene_texture = glium::texture::Texture2d::empty(facade, width, height)
+            .context("could not reallocate offscreen scene texture")?;
+        Ok(())
+    }
+
+    /// Runs `draw_scene` against an offscreen framebuffer, then composites the
+    /// result onto `target` through the CRT shader.
+    ///
+    /// The `SimpleFrameBuffer` is rebuilt every call rather than cached on
+    /// `PostProcess`: it borrows `scene_texture`, so storing one alongside the
+    /// texture it borrows would make `PostProcess` self-referential. Building it
+    /// here is cheap — it's a thin handle around the texture's already-allocated
+    /// GL object, not a fresh GPU allocation.
+    pub fn draw<F: glium::backend::Facade>(
+        &mut self,
+        facade: &F,
+        target: &mut glium::Frame,
+        settings: &CrtSettings,
+        draw_scene: impl FnOnce(&mut glium::framebuffer::SimpleFrameBuffer) -> Result<()>,
+    ) -> Result<()> {
+        self.program.poll(facade)?;
+
+        let mut fbo = glium::framebuffer::SimpleFrameBuffer::new(facade, &self.scene_texture)
+            .context("could not build offscreen framebuffer")?;
+        draw_scene(&mut fbo)?;
+
+        let uniforms = uniform! {
+            scene: self.scene_texture.sampled()
+                .magnify_filter(glium::uniforms::MagnifySamplerFilter::Linear),
+            curvature: settings.curvature,
+            scanline_opacity: settings.scanline_opacity,
+            brightness: settings.brightness,
+        };
+
+        target
+            .draw(
+                &self.quad,
+                &self.indices,
+                self.program.program(),
+                &uniforms,
+                &Default::default(),
+            )
+            .context("could not draw post-process pass")
+    }
+}
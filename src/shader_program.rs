@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A `glium::Program` built from vertex/fragment source files on disk, polled
+/// for changes so saving a shader rebuilds it without recompiling the crate.
+/// A failed rebuild is logged and the last good program keeps running instead
+/// of panicking.
+pub struct ShaderProgram {
+    vertex_path: PathBuf,
+    fragment_path: PathBuf,
+    vertex_modified: SystemTime,
+    fragment_modified: SystemTime,
+    program: glium::program::Program,
+}
+
+impl ShaderProgram {
+    pub fn new<F: glium::backend::Facade>(
+        facade: &F,
+        vertex_path: impl AsRef<Path>,
+        fragment_path: impl AsRef<Path>,
+    ) -> Result<ShaderProgram> {
+        let vertex_path = vertex_path.as_ref().to_path_buf();
+        let fragment_path = fragment_path.as_ref().to_path_buf();
+
+        let program = Self::compile(facade, &vertex_path, &fragment_path)?;
+        let vertex_modified = modified(&vertex_path)?;
+        let fragment_modified = modified(&fragment_path)?;
+
+        Ok(ShaderProgram {
+            vertex_path,
+            fragment_path,
+            vertex_modified,
+            fragment_modified,
+            program,
+        })
+    }
+
+    pub fn program(&self) -> &glium::program::Program {
+        &self.program
+    }
+
+    /// Call once per frame (e.g. from a `Renderable::update`). If either source
+    /// file's mtime has moved on, recompiles the program; on a compile error the
+    /// error is logged to stderr and the previous, still-working program is kept.
+    pub fn poll<F: glium::backend::Facade>(&mut self, facade: &F) -> Result<()> {
+        // An editor's atomic save (write temp file + rename) can make the file
+        // briefly unstatable; treat that as "nothing to reload yet" rather than
+        // a hard error, since the whole point of this type is to never panic
+        // the renderer over a shader edit.
+        let (vertex_modified, fragment_modified) =
+            match (modified(&self.vertex_path), modified(&self.fragment_path)) {
+                (Ok(v), Ok(f)) => (v, f),
+                _ => return Ok(()),
+            };
+
+        if vertex_modified == self.vertex_modified && fragment_modified == self.fragment_modified {
+            return Ok(());
+        }
+
+        match Self::compile(facade, &self.vertex_path, &self.fragment_path) {
+            Ok(program) => {
+                self.program = program;
+                self.vertex_modified = vertex_modified;
+                self.fragment_modified = fragment_modified;
+            }
+            Err(err) => {
+                eprintln!(
+                    "shader reload failed for {} / {}: {err:#}",
+                    self.vertex_path.display(),
+                    self.fragment_path.display()
+                );
+                // Keep the mtimes as last-seen so we don't retry the same
+                // broken compile every frame until the file changes again.
+                self.vertex_modified = vertex_modified;
+                self.fragment_modified = fragment_modified;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn compile<F: glium::backend::Facade>(
+        facade: &F,
+        vertex_path: &Path,
+        fragment_path: &Path,
+    ) -> Result<glium::program::Program> {
+        let vertex_src = std::fs::read_to_string(vertex_path)
+            .with_context(|| format!("could not read {}", vertex_path.display()))?;
+        let fragment_src = std::fs::read_to_string(fragment_path)
+            .with_context(|| format!("could not read {}", fragment_path.display()))?;
+
+        glium::program::Program::from_source(facade, &vertex_src, &fragment_src, None)
+            .with_context(|| {
+                format!(
+                    "could not compile {} / {}",
+                    vertex_path.display(),
+                    fragment_path.display()
+                )
+            })
+    }
+}
+
+fn modified(path: &Path) -> Result<SystemTime> {
+    std::fs::metadata(path)
+        .with_context(|| format!("could not stat {}", path.display()))?
+        .modified()
+        .with_context(|| format!("no mtime for {}", path.display()))
+}
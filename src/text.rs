@@ -0,0 +1,202 @@
+use anyhow::{Context, Result};
+use glium::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::shader_program::ShaderProgram;
+use crate::{Renderable, TexVertex};
+
+/// A single `Texture2d` shared by every `Text`, with glyphs rasterized into free
+/// cells on demand the first time they're requested.
+pub struct GlyphCache {
+    cache: rusttype::gpu_cache::Cache<'static>,
+    texture: glium::texture::Texture2d,
+}
+
+impl GlyphCache {
+    pub fn new<F: glium::backend::Facade>(facade: &F, width: u32, height: u32) -> Result<GlyphCache> {
+        let cache = rusttype::gpu_cache::Cache::builder()
+            .dimensions(width, height)
+            .build();
+        let texture = glium::texture::Texture2d::empty_with_format(
+            facade,
+            glium::texture::UncompressedFloatFormat::U8,
+            glium::texture::MipmapsOption::NoMipmap,
+            width,
+            height,
+        )
+        .context("could not allocate glyph cache texture")?;
+
+        Ok(GlyphCache { cache, texture })
+    }
+
+    pub fn texture(&self) -> &glium::texture::Texture2d {
+        &self.texture
+    }
+
+    fn queue(&mut self, glyphs: &[rusttype::PositionedGlyph<'static>]) {
+        for glyph in glyphs {
+            self.cache.queue_glyph(0, glyph.clone());
+        }
+    }
+
+    fn upload_queued(&mut self) -> Result<()> {
+        let texture = &self.texture;
+        self.cache
+            .cache_queued(|rect, data| {
+                texture.main_level().write(
+                    glium::Rect {
+                        left: rect.min.x,
+                        bottom: rect.min.y,
+                        width: rect.width(),
+                        height: rect.height(),
+                    },
+                    glium::texture::RawImage2d {
+                        data: std::borrow::Cow::Borrowed(data),
+                        width: rect.width(),
+                        height: rect.height(),
+                        format: glium::texture::ClientFormat::U8,
+                    },
+                );
+            })
+            .map(|_cached_by| ())
+            .context("could not pack queued glyphs into the cache texture")
+    }
+
+    fn rect_for(&self, glyph: &rusttype::PositionedGlyph<'static>) -> Option<(rusttype::Rect<f32>, rusttype::Rect<i32>)> {
+        self.cache.rect_for(0, glyph).ok().flatten()
+    }
+}
+
+/// Draws a laid-out string as textured quads sampled from a shared [`GlyphCache`].
+/// Used for FPS/debug overlays; `clip` confines the glyphs to a rectangle via the
+/// draw parameters' scissor test.
+pub struct Text {
+    glyph_cache: Rc<RefCell<GlyphCache>>,
+    program: Rc<RefCell<ShaderProgram>>,
+    vertices: glium::vertex::VertexBuffer<TexVertex>,
+    indices: glium::IndexBuffer<u32>,
+    clip: Option<glium::Rect>,
+}
+
+impl Text {
+    pub fn program<F: glium::backend::Facade>(facade: &F) -> Result<ShaderProgram> {
+        ShaderProgram::new(facade, "assets/text.vert", "assets/text.frag")
+            .context("could not load text shaders")
+    }
+
+    /// Lays out `text` at `position` (in normalized device coordinates) using
+    /// `font` at `scale` pixels, queues the glyphs into `glyph_cache` and builds
+    /// the quads that sample them back out.
+    pub fn new<F: glium::backend::Facade>(
+        facade: &F,
+        glyph_cache: Rc<RefCell<GlyphCache>>,
+        program: Rc<RefCell<ShaderProgram>>,
+        font: &'static rusttype::Font<'static>,
+        text: &str,
+        position: [f32; 2],
+        scale: f32,
+        screen_size: (f32, f32),
+        clip: Option<glium::Rect>,
+    ) -> Result<Text> {
+        let glyphs = layout(font, text, scale, position, screen_size);
+
+        {
+            let mut cache = glyph_cache.borrow_mut();
+            cache.queue(&glyphs);
+            cache.upload_queued()?;
+        }
+
+        let cache = glyph_cache.borrow();
+        let mut shape = Vec::with_capacity(glyphs.len() * 4);
+        let mut indices = Vec::with_capacity(glyphs.len() * 6);
+        for glyph in &glyphs {
+            let Some((uv, px)) = cache.rect_for(glyph) else {
+                continue;
+            };
+            let base = shape.len() as u32;
+            let (sx, sy) = screen_size;
+            let to_ndc = |x: f32, y: f32| [x / sx * 2.0 - 1.0, 1.0 - y / sy * 2.0];
+
+            shape.push(TexVertex {
+                position: to_ndc(px.min.x as f32, px.max.y as f32),
+                tex_coords: [uv.min.x, uv.max.y],
+            });
+            shape.push(TexVertex {
+                position: to_ndc(px.min.x as f32, px.min.y as f32),
+                tex_coords: [uv.min.x, uv.min.y],
+            });
+            shape.push(TexVertex {
+                position: to_ndc(px.max.x as f32, px.min.y as f32),
+                tex_coords: [uv.max.x, uv.min.y],
+            });
+            shape.push(TexVertex {
+                position: to_ndc(px.max.x as f32, px.max.y as f32),
+                tex_coords: [uv.max.x, uv.max.y],
+            });
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        drop(cache);
+
+        let vertices = glium::VertexBuffer::persistent(facade, &shape).context("no vertices")?;
+        let indices =
+            glium::IndexBuffer::new(facade, glium::index::PrimitiveType::TrianglesList, &indices)
+                .context("no index")?;
+
+        Ok(Text {
+            glyph_cache,
+            program,
+            vertices,
+            indices,
+            clip,
+        })
+    }
+}
+
+fn layout(
+    font: &'static rusttype::Font<'static>,
+    text: &str,
+    scale: f32,
+    position: [f32; 2],
+    screen_size: (f32, f32),
+) -> Vec<rusttype::PositionedGlyph<'static>> {
+    let (sx, sy) = screen_size;
+    let origin = rusttype::point(
+        (position[0] + 1.0) * 0.5 * sx,
+        (1.0 - position[1]) * 0.5 * sy,
+    );
+    font.layout(text, rusttype::Scale::uniform(scale), origin)
+        .collect()
+}
+
+impl Renderable for Text {
+    fn update<F: glium::backend::Facade>(&mut self, facade: &F, _delta: std::time::Duration) -> Result<()> {
+        self.program.borrow_mut().poll(facade)
+    }
+
+    fn custom_render<S: glium::Surface>(
+        &self,
+        frame: &mut S,
+        matrix: [[f32; 4]; 4],
+        params: &glium::draw_parameters::DrawParameters,
+    ) -> Result<(), glium::DrawError> {
+        let mut params = params.clone();
+        if let Some(clip) = self.clip {
+            params.scissor = Some(clip);
+        }
+
+        let glyph_cache = self.glyph_cache.borrow();
+        let uniforms = uniform! {
+            glyphs: glyph_cache.texture(),
+            matrix: matrix,
+        };
+
+        frame.draw(
+            &self.vertices,
+            &self.indices,
+            self.program.borrow().program(),
+            &uniforms,
+            &params,
+        )
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+/// The four normalized UV corners of a sub-image packed into a [`TextureAtlas`].
+pub struct AtlasUv {
+    pub bottom_left: [f32; 2],
+    pub bottom_right: [f32; 2],
+    pub top_left: [f32; 2],
+    pub top_right: [f32; 2],
+}
+
+/// Packs many PNGs glob'd from disk into a single `SrgbTexture2d`, so a bunch of
+/// sprites can share one texture and, via `AtlasBatch`, one draw call instead
+/// of one each.
+pub struct TextureAtlas {
+    texture: Rc<glium::texture::SrgbTexture2d>,
+    regions: HashMap<String, glium::Rect>,
+}
+
+impl TextureAtlas {
+    /// Globs `pattern` (e.g. `"assets/textures/**/*.png"`), decodes every match and
+    /// packs them into one texture using a simple shelf bin-packer: rects are sorted
+    /// by descending height, placed left-to-right into rows of the current row's
+    /// height, and a new row opens once the running width would exceed `max_width`.
+    pub fn from_glob<F: glium::backend::Facade>(
+        facade: &F,
+        pattern: &str,
+        max_width: u32,
+    ) -> Result<TextureAtlas> {
+        let images: Vec<(String, image::RgbaImage)> = glob::glob(pattern)
+            .context("invalid glob pattern")?
+            .filter_map(std::result::Result::ok)
+            .map(|path| -> Result<(String, image::RgbaImage)> {
+                let stem = Self::stem_of(&path)?;
+                let img = image::open(&path)
+                    .with_context(|| format!("could not decode {}", path.display()))?
+                    .to_rgba8();
+                Ok((stem, img))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let sizes: Vec<(String, u32, u32)> = images
+            .iter()
+            .map(|(name, img)| (name.clone(), img.width(), img.height()))
+            .collect();
+        let (regions, atlas_width, atlas_height) = pack_shelves(&sizes, max_width);
+
+        let mut pixels = vec![0u8; (atlas_width as usize) * (atlas_height as usize) * 4];
+        for (name, img) in &images {
+            let rect = regions[name];
+            blit(&mut pixels, atlas_width, img, &rect);
+        }
+
+        let raw = glium::texture::RawImage2d::from_raw_rgba(pixels, (atlas_width, atlas_height));
+        let texture = glium::texture::SrgbTexture2d::with_mipmaps(
+            facade,
+            raw,
+            glium::texture::MipmapsOption::AutoGeneratedMipmaps,
+        )
+        .context("could not build atlas texture")?;
+
+        Ok(TextureAtlas {
+            texture: Rc::new(texture),
+            regions,
+        })
+    }
+
+    /// A cheap handle to the backing texture, for renderables that want to hold
+    /// onto it (e.g. many quads sharing one atlas) without borrowing the atlas.
+    pub fn texture_rc(&self) -> Rc<glium::texture::SrgbTexture2d> {
+        self.texture.clone()
+    }
+
+    /// Normalized UV corners for the sub-image named `name`, or `None` if it wasn't
+    /// packed into this atlas.
+    pub fn uv_of(&self, name: &str) -> Option<AtlasUv> {
+        let rect = self.regions.get(name)?;
+        Some(uv_of_rect(rect, self.texture.width(), self.texture.height()))
+    }
+
+    fn stem_of(path: &Path) -> Result<String> {
+        path.file_stem()
+            .and_then(|s| s.to_str())
+            .map(str::to_owned)
+            .with_context(|| format!("no file stem for {}", path.display()))
+    }
+}
+
+fn blit(dst: &mut [u8], dst_width: u32, src: &image::RgbaImage, rect: &glium::Rect) {
+    for y in 0..rect.height {
+        let src_row = &src.as_raw()[(y * src.width() * 4) as usize..][..(src.width() * 4) as usize];
+        let dst_y = rect.bottom + y;
+        let dst_start = ((dst_y * dst_width + rect.left) * 4) as usize;
+        dst[dst_start..dst_start + src_row.len()].copy_from_slice(src_row);
+    }
+}
+
+/// The shelf bin-packer itself, kept free of `image`/GL types so it can be unit
+/// tested directly: given `(name, width, height)` triples, sorts tallest-first,
+/// places left-to-right into rows of the current row's height, and opens a new
+/// row once the running width would exceed `max_width`. Returns each name's
+/// packed rect plus the resulting atlas dimensions.
+fn pack_shelves(sizes: &[(String, u32, u32)], max_width: u32) -> (HashMap<String, glium::Rect>, u32, u32) {
+    let mut sizes = sizes.to_vec();
+    // Tallest first: a shelf packer wastes the least space this way, since every
+    // row only loses the difference between its tallest and shortest occupant.
+    sizes.sort_by_key(|(_, _, h)| std::cmp::Reverse(*h));
+
+    let mut regions = HashMap::with_capacity(sizes.len());
+
+    let mut cursor_x = 0u32;
+    let mut cursor_y = 0u32;
+    let mut row_height = 0u32;
+    let mut atlas_width = 0u32;
+
+    for (name, w, h) in &sizes {
+        if cursor_x != 0 && cursor_x + w > max_width {
+            cursor_y += row_height;
+            cursor_x = 0;
+            row_height = 0;
+        }
+
+        regions.insert(
+            name.clone(),
+            glium::Rect {
+                left: cursor_x,
+                bottom: cursor_y,
+                width: *w,
+                height: *h,
+            },
+        );
+
+        cursor_x += w;
+        atlas_width = atlas_width.max(cursor_x);
+        row_height = row_height.max(*h);
+    }
+    let atlas_height = cursor_y + row_height;
+
+    (regions, atlas_width, atlas_height)
+}
+
+/// Normalized UV corners for `rect` within an atlas of size `atlas_width` x
+/// `atlas_height`, kept free of any live texture so it can be unit tested.
+fn uv_of_rect(rect: &glium::Rect, atlas_width: u32, atlas_height: u32) -> AtlasUv {
+    let (w, h) = (atlas_width as f32, atlas_height as f32);
+    let left = rect.left as f32 / w;
+    let right = (rect.left + rect.width) as f32 / w;
+    let bottom = rect.bottom as f32 / h;
+    let top = (rect.bottom + rect.height) as f32 / h;
+    AtlasUv {
+        bottom_left: [left, bottom],
+        bottom_right: [right, bottom],
+        top_left: [left, top],
+        top_right: [right, top],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sizes(entries: &[(&str, u32, u32)]) -> Vec<(String, u32, u32)> {
+        entries
+            .iter()
+            .map(|(name, w, h)| (name.to_string(), *w, *h))
+            .collect()
+    }
+
+    fn rect_eq(rect: &glium::Rect, left: u32, bottom: u32, width: u32, height: u32) {
+        assert_eq!((rect.left, rect.bottom, rect.width, rect.height), (left, bottom, width, height));
+    }
+
+    #[test]
+    fn packs_a_single_row_when_everything_fits() {
+        let (regions, atlas_width, atlas_height) =
+            pack_shelves(&sizes(&[("a", 10, 20), ("b", 10, 10)]), 100);
+
+        assert_eq!(atlas_width, 20);
+        assert_eq!(atlas_height, 20);
+        rect_eq(&regions["a"], 0, 0, 10, 20);
+        rect_eq(&regions["b"], 10, 0, 10, 10);
+    }
+
+    #[test]
+    fn opens_a_new_row_on_overflow() {
+        // Tallest ("tall") is placed first; "wide" then overflows max_width and
+        // starts a second row below it.
+        let (regions, atlas_width, atlas_height) =
+            pack_shelves(&sizes(&[("tall", 10, 30), ("wide", 10, 10)]), 15);
+
+        assert_eq!(atlas_width, 10);
+        assert_eq!(atlas_height, 40);
+        rect_eq(&regions["tall"], 0, 0, 10, 30);
+        rect_eq(&regions["wide"], 0, 30, 10, 10);
+    }
+
+    #[test]
+    fn uv_of_rect_computes_normalized_corners() {
+        let rect = glium::Rect { left: 10, bottom: 20, width: 30, height: 40 };
+        let uv = uv_of_rect(&rect, 100, 200);
+
+        assert_eq!(uv.bottom_left, [0.1, 0.1]);
+        assert_eq!(uv.bottom_right, [0.4, 0.1]);
+        assert_eq!(uv.top_left, [0.1, 0.3]);
+        assert_eq!(uv.top_right, [0.4, 0.3]);
+    }
+}